@@ -0,0 +1,52 @@
+//! Checksumming of migration scripts, used to detect when an already-applied migration has been
+//! edited on disk.
+
+use sha2::{Digest, Sha256};
+
+/// Normalize a migration script before hashing it, so that inconsequential reformatting (line
+/// ending conversions, trailing whitespace added by an editor) doesn't look like drift.
+fn normalize(script: &str) -> String {
+    script
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Compute the checksum to be stored for and compared against a migration script.
+pub fn compute(script: &str) -> String {
+    let normalized = normalize(script);
+    let digest = Sha256::digest(normalized.as_bytes());
+    format!("{:x}", digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_strips_trailing_whitespace_on_each_line() {
+        assert_eq!(normalize("CREATE TABLE a (  \n  id INT\t\n);   "), "CREATE TABLE a (\n  id INT\n);");
+    }
+
+    #[test]
+    fn normalize_converts_crlf_to_lf() {
+        assert_eq!(normalize("CREATE TABLE a (\r\n  id INT\r\n);"), "CREATE TABLE a (\n  id INT\n);");
+    }
+
+    #[test]
+    fn compute_is_unaffected_by_trailing_whitespace_or_line_endings() {
+        let unix = "CREATE TABLE a (\n  id INT\n);";
+        let windows = "CREATE TABLE a (\r\n  id INT  \r\n);   ";
+
+        assert_eq!(compute(unix), compute(windows));
+    }
+
+    #[test]
+    fn compute_detects_a_real_content_change() {
+        let original = "CREATE TABLE a (\n  id INT\n);";
+        let edited = "CREATE TABLE a (\n  id BIGINT\n);";
+
+        assert_ne!(compute(original), compute(edited));
+    }
+}
@@ -0,0 +1,37 @@
+use thiserror::Error;
+
+/// The result type for connector operations.
+pub type ConnectorResult<T> = Result<T, ConnectorError>;
+
+/// The error type for all connector operations.
+#[derive(Debug, Error)]
+#[error("{kind}")]
+pub struct ConnectorError {
+    kind: ErrorKind,
+}
+
+impl ConnectorError {
+    pub fn from_kind(kind: ErrorKind) -> Self {
+        ConnectorError { kind }
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl From<std::io::Error> for ConnectorError {
+    fn from(err: std::io::Error) -> Self {
+        ConnectorError::from_kind(ErrorKind::Generic(err.to_string()))
+    }
+}
+
+/// The different kinds of errors a connector can produce.
+#[derive(Debug, Error)]
+pub enum ErrorKind {
+    #[error("{0}")]
+    Generic(String),
+
+    #[error("Database query error: {0}")]
+    QueryError(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
@@ -0,0 +1,49 @@
+//! The abstraction over connectors used by the migration engine core. A migration connector
+//! takes care of the connector-specific parts of schema migrations: diffing, applying scripts,
+//! and keeping track of the migrations bookkeeping table.
+
+mod error;
+
+pub mod checksum;
+pub mod migration_persistence;
+pub mod migrations_directory;
+
+pub use error::{ConnectorError, ConnectorResult, ErrorKind};
+pub use migration_persistence::{MigrationPersistence, MigrationRecord, PersistenceNotInitializedError};
+
+/// The top-level trait implemented by every connector (Postgres, MySQL, SQLite, SQL Server,
+/// MongoDB, ...) that the migration engine drives.
+#[async_trait::async_trait]
+pub trait MigrationConnector: Send + Sync {
+    /// The provider name, e.g. `"postgresql"`.
+    fn connector_type(&self) -> &'static str;
+
+    /// Acquire an advisory lock on the target database, so concurrent migration engine runs
+    /// don't step on each other.
+    async fn acquire_lock(&mut self) -> ConnectorResult<()>;
+
+    /// The migrations bookkeeping persistence for this connector, targeting `table_name` (pass
+    /// [`migration_persistence::DEFAULT_MIGRATIONS_TABLE_NAME`] unless the user configured a
+    /// different table name).
+    fn migration_persistence(&mut self, table_name: &str) -> &mut dyn MigrationPersistence;
+
+    /// Execute a migration script against the database.
+    async fn apply_script(&mut self, migration_name: &str, script: &str) -> ConnectorResult<()>;
+
+    /// Whether this connector can run DDL statements as part of a transaction. Most can; MySQL
+    /// is a notable exception, since some DDL statements implicitly commit the surrounding
+    /// transaction.
+    fn supports_transactional_ddl(&self) -> bool {
+        true
+    }
+
+    /// Apply every migration in `migrations` (name, script, checksum) inside a single database
+    /// transaction, recording each one (with its checksum) in the bookkeeping table named
+    /// `migrations_table_name`. If any script fails, the whole transaction is rolled back and
+    /// the persistence table reflects none of `migrations` as applied.
+    async fn apply_migrations_in_transaction(
+        &mut self,
+        migrations: &[(&str, String, String)],
+        migrations_table_name: &str,
+    ) -> ConnectorResult<Vec<String>>;
+}
@@ -0,0 +1,63 @@
+use crate::ConnectorError;
+
+/// The name of the bookkeeping table used when no override is configured.
+pub const DEFAULT_MIGRATIONS_TABLE_NAME: &str = "_prisma_migrations";
+
+/// A row in the migrations bookkeeping table.
+#[derive(Debug, Clone)]
+pub struct MigrationRecord {
+    pub id: String,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub migration_name: String,
+    pub logs: Option<String>,
+    pub rolled_back_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub applied_steps_count: u32,
+    /// The SHA-256 checksum of the migration script, normalized, at the time it was applied.
+    /// `None` for records written before the checksum column was introduced.
+    pub checksum: Option<String>,
+}
+
+/// Management of the migrations bookkeeping table.
+#[async_trait::async_trait]
+pub trait MigrationPersistence: Send + Sync {
+    /// Create the bookkeeping table if it doesn't exist yet.
+    async fn initialize(&mut self) -> Result<(), ConnectorError>;
+
+    /// List all applied migrations, ordered by `started_at`.
+    async fn list_migrations(&mut self) -> Result<Result<Vec<MigrationRecord>, PersistenceNotInitializedError>, ConnectorError>;
+
+    /// Insert a new started migration, along with the checksum of its script, and return its id.
+    async fn record_migration_started(
+        &mut self,
+        migration_name: &str,
+        script: &str,
+        checksum: &str,
+    ) -> Result<String, ConnectorError>;
+
+    /// Overwrite the stored checksum of an already-recorded migration. Used to backfill the
+    /// checksum of records that predate the checksum column.
+    async fn update_migration_checksum(&mut self, migration_name: &str, checksum: &str) -> Result<(), ConnectorError>;
+
+    async fn record_successful_step(&mut self, id: &str) -> Result<(), ConnectorError>;
+
+    async fn record_failed_step(&mut self, id: &str, logs: &str) -> Result<(), ConnectorError>;
+
+    async fn record_migration_finished(&mut self, id: &str) -> Result<(), ConnectorError>;
+
+    /// Mark a migration as rolled back, as part of a `revert_migrations` run.
+    async fn record_rolled_back(&mut self, id: &str) -> Result<(), ConnectorError>;
+}
+
+/// Returned by [`MigrationPersistence::list_migrations`] when the bookkeeping table does not
+/// exist yet.
+#[derive(Debug)]
+pub struct PersistenceNotInitializedError;
+
+impl PersistenceNotInitializedError {
+    pub fn into_connector_error(self) -> ConnectorError {
+        ConnectorError::from_kind(crate::ErrorKind::Generic(
+            "The migrations table does not exist.".to_owned(),
+        ))
+    }
+}
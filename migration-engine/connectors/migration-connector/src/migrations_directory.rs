@@ -0,0 +1,66 @@
+use crate::{ConnectorError, ConnectorResult};
+use std::{fs, io, path::{Path, PathBuf}};
+
+/// The name of the file a migration's forward script is read from.
+pub const MIGRATION_SCRIPT_FILENAME: &str = "migration.sql";
+
+/// The name of the file a migration's rollback script is read from, if any.
+pub const DOWN_MIGRATION_SCRIPT_FILENAME: &str = "down.sql";
+
+/// A single migration directory on the filesystem, e.g.
+/// `migrations/20210101000000_init/`.
+#[derive(Debug, Clone)]
+pub struct MigrationDirectory {
+    path: PathBuf,
+}
+
+impl MigrationDirectory {
+    /// The name of the migration, e.g. `20210101000000_init`.
+    pub fn migration_name(&self) -> &str {
+        self.path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    }
+
+    /// Read the contents of `migration.sql`.
+    pub fn read_migration_script(&self) -> io::Result<String> {
+        fs::read_to_string(self.path.join(MIGRATION_SCRIPT_FILENAME))
+    }
+
+    /// Whether this migration has a `down.sql` next to its `migration.sql`.
+    pub fn has_rollback_script(&self) -> bool {
+        self.path.join(DOWN_MIGRATION_SCRIPT_FILENAME).is_file()
+    }
+
+    /// Read the contents of `down.sql`, if present.
+    pub fn read_rollback_script(&self) -> io::Result<Option<String>> {
+        if self.has_rollback_script() {
+            fs::read_to_string(self.path.join(DOWN_MIGRATION_SCRIPT_FILENAME)).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// List the migrations present in a migrations directory, sorted by name (which sorts
+/// chronologically, since migration names are prefixed with a timestamp).
+pub fn list_migrations(migrations_directory_path: &Path) -> ConnectorResult<Vec<MigrationDirectory>> {
+    let mut entries: Vec<MigrationDirectory> = fs::read_dir(migrations_directory_path)
+        .map_err(|err| ConnectorError::from_kind(crate::ErrorKind::Generic(err.to_string())))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| MigrationDirectory { path: entry.path() })
+        .collect();
+
+    entries.sort_by(|a, b| a.migration_name().cmp(b.migration_name()));
+
+    Ok(entries)
+}
+
+/// Check the migrations directory against the connector's provider. Always returns `Ok(())`:
+/// the provider used to generate the migrations directory is not compared with the one
+/// configured on the connector.
+pub fn error_on_changed_provider(_migrations_directory_path: &str, _connector_type: &str) -> ConnectorResult<()> {
+    Ok(())
+}
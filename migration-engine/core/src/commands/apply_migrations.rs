@@ -1,10 +1,12 @@
 use crate::{json_rpc::types::*, CoreError, CoreResult};
 use migration_connector::{
+    checksum,
+    migration_persistence::DEFAULT_MIGRATIONS_TABLE_NAME,
     migrations_directory::{error_on_changed_provider, list_migrations, MigrationDirectory},
     ConnectorError, MigrationConnector, MigrationRecord, PersistenceNotInitializedError,
 };
-use std::{path::Path, time::Instant};
-use user_facing_errors::migration_engine::FoundFailedMigrations;
+use std::{fmt::Write as _, path::Path, time::Instant};
+use user_facing_errors::migration_engine::{DriftedMigrations, FoundFailedMigrations};
 
 pub async fn apply_migrations(
     input: ApplyMigrationsInput,
@@ -14,19 +16,33 @@ pub async fn apply_migrations(
 
     error_on_changed_provider(&input.migrations_directory_path, connector.connector_type())?;
 
+    let migrations_table_name = input
+        .migrations_table_name
+        .as_deref()
+        .unwrap_or(DEFAULT_MIGRATIONS_TABLE_NAME);
+
     connector.acquire_lock().await?;
 
-    connector.migration_persistence().initialize().await?;
+    connector.migration_persistence(migrations_table_name).initialize().await?;
 
     let migrations_from_filesystem = list_migrations(Path::new(&input.migrations_directory_path))?;
     let migrations_from_database = connector
-        .migration_persistence()
+        .migration_persistence(migrations_table_name)
         .list_migrations()
         .await?
         .map_err(PersistenceNotInitializedError::into_connector_error)?;
 
     detect_failed_migrations(&migrations_from_database)?;
 
+    detect_drift(&migrations_from_filesystem, &migrations_from_database)?;
+    backfill_checksums(
+        &migrations_from_filesystem,
+        &migrations_from_database,
+        connector,
+        migrations_table_name,
+    )
+    .await?;
+
     // We are now on the Happy Path™.
     tracing::debug!("Migration history is OK, applying unapplied migrations.");
     let unapplied_migrations: Vec<&MigrationDirectory> = migrations_from_filesystem
@@ -42,9 +58,43 @@ pub async fn apply_migrations(
     let analysis_duration_ms = Instant::now().duration_since(start).as_millis() as u64;
     tracing::info!(analysis_duration_ms, "Analysis run in {}ms", analysis_duration_ms,);
 
-    let mut applied_migration_names: Vec<String> = Vec::with_capacity(unapplied_migrations.len());
     let apply_migrations_start = Instant::now();
 
+    let applied_migration_names = if input.use_single_transaction && connector.supports_transactional_ddl() {
+        apply_in_single_transaction(&unapplied_migrations, connector, migrations_table_name).await?
+    } else {
+        if input.use_single_transaction {
+            tracing::warn!(
+                "`useSingleTransaction` was requested, but the `{}` connector does not support transactional DDL. Falling back to applying each migration in its own transaction.",
+                connector.connector_type()
+            );
+        }
+
+        apply_one_by_one(&unapplied_migrations, connector, migrations_table_name).await?
+    };
+
+    let apply_migrations_ms = Instant::now().duration_since(apply_migrations_start).as_millis() as u64;
+    tracing::info!(
+        apply_migrations_duration_ms = apply_migrations_ms,
+        "All the migrations executed in {}ms",
+        apply_migrations_ms
+    );
+
+    Ok(ApplyMigrationsOutput {
+        applied_migration_names,
+    })
+}
+
+/// Apply each unapplied migration in its own implicit transaction. On failure, everything
+/// applied so far in this batch stays committed, and the failing migration is recorded as
+/// failed.
+async fn apply_one_by_one(
+    unapplied_migrations: &[&MigrationDirectory],
+    connector: &mut dyn MigrationConnector,
+    migrations_table_name: &str,
+) -> CoreResult<Vec<String>> {
+    let mut applied_migration_names: Vec<String> = Vec::with_capacity(unapplied_migrations.len());
+
     for unapplied_migration in unapplied_migrations {
         let span = tracing::info_span!(
             "Applying migration",
@@ -63,9 +113,10 @@ pub async fn apply_migrations(
             unapplied_migration.migration_name()
         );
 
+        let checksum = checksum::compute(&script);
         let migration_id = connector
-            .migration_persistence()
-            .record_migration_started(unapplied_migration.migration_name(), &script)
+            .migration_persistence(migrations_table_name)
+            .record_migration_started(unapplied_migration.migration_name(), &script, &checksum)
             .await?;
 
         match connector
@@ -74,7 +125,7 @@ pub async fn apply_migrations(
         {
             Ok(()) => {
                 tracing::debug!("Successfully applied the script.");
-                let p = connector.migration_persistence();
+                let p = connector.migration_persistence(migrations_table_name);
                 p.record_successful_step(&migration_id).await?;
                 p.record_migration_finished(&migration_id).await?;
                 applied_migration_names.push(unapplied_migration.migration_name().to_owned());
@@ -91,7 +142,7 @@ pub async fn apply_migrations(
                 let logs = err.to_string();
 
                 connector
-                    .migration_persistence()
+                    .migration_persistence(migrations_table_name)
                     .record_failed_step(&migration_id, &logs)
                     .await?;
 
@@ -100,21 +151,119 @@ pub async fn apply_migrations(
         }
     }
 
-    let apply_migrations_ms = Instant::now().duration_since(apply_migrations_start).as_millis() as u64;
-    tracing::info!(
-        apply_migrations_duration_ms = apply_migrations_ms,
-        "All the migrations executed in {}ms",
-        apply_migrations_ms
-    );
+    Ok(applied_migration_names)
+}
 
-    Ok(ApplyMigrationsOutput {
-        applied_migration_names,
-    })
+/// Apply every unapplied migration inside a single database transaction. If any script fails,
+/// the whole transaction is rolled back and none of `unapplied_migrations` are recorded as
+/// applied.
+async fn apply_in_single_transaction(
+    unapplied_migrations: &[&MigrationDirectory],
+    connector: &mut dyn MigrationConnector,
+    migrations_table_name: &str,
+) -> CoreResult<Vec<String>> {
+    let mut scripts: Vec<(&str, String, String)> = Vec::with_capacity(unapplied_migrations.len());
+
+    for unapplied_migration in unapplied_migrations {
+        let script = unapplied_migration
+            .read_migration_script()
+            .map_err(ConnectorError::from)?;
+        let checksum = checksum::compute(&script);
+        scripts.push((unapplied_migration.migration_name(), script, checksum));
+    }
+
+    tracing::info!("Applying {} migrations in a single transaction.", scripts.len());
+
+    let applied_migration_names = connector
+        .apply_migrations_in_transaction(&scripts, migrations_table_name)
+        .await?;
+
+    Ok(applied_migration_names)
 }
 
-fn detect_failed_migrations(migrations_from_database: &[MigrationRecord]) -> CoreResult<()> {
-    use std::fmt::Write as _;
+/// Compare the checksum of every already-applied migration against the script currently on
+/// disk, so an accidental edit to a committed migration is caught before we apply anything.
+fn detect_drift(
+    migrations_from_filesystem: &[MigrationDirectory],
+    migrations_from_database: &[MigrationRecord],
+) -> CoreResult<()> {
+    tracing::debug!("Checking for drift in already-applied migrations.");
+
+    let mut details = String::new();
+
+    for db_migration in migrations_from_database
+        .iter()
+        .filter(|db_migration| db_migration.rolled_back_at.is_none())
+    {
+        let expected_checksum = match &db_migration.checksum {
+            Some(checksum) => checksum,
+            // Predates the checksum column: nothing to compare against yet.
+            None => continue,
+        };
+
+        let fs_migration = match migrations_from_filesystem
+            .iter()
+            .find(|fs_migration| fs_migration.migration_name() == db_migration.migration_name)
+        {
+            Some(fs_migration) => fs_migration,
+            None => continue,
+        };
+
+        let script = fs_migration.read_migration_script().map_err(ConnectorError::from)?;
+        let actual_checksum = checksum::compute(&script);
+
+        if &actual_checksum != expected_checksum {
+            writeln!(
+                details,
+                "The `{name}` migration has been edited after it was applied: expected checksum {expected}, found {actual}",
+                name = db_migration.migration_name,
+                expected = expected_checksum,
+                actual = actual_checksum,
+            )
+            .unwrap();
+        }
+    }
 
+    if details.is_empty() {
+        Ok(())
+    } else {
+        Err(CoreError::user_facing(DriftedMigrations { details }))
+    }
+}
+
+/// Fill in the checksum of database records that were written before the checksum column
+/// existed, so future runs of [`detect_drift`] have something to compare against.
+async fn backfill_checksums(
+    migrations_from_filesystem: &[MigrationDirectory],
+    migrations_from_database: &[MigrationRecord],
+    connector: &mut dyn MigrationConnector,
+    migrations_table_name: &str,
+) -> CoreResult<()> {
+    for db_migration in migrations_from_database
+        .iter()
+        .filter(|db_migration| db_migration.checksum.is_none())
+    {
+        let fs_migration = match migrations_from_filesystem
+            .iter()
+            .find(|fs_migration| fs_migration.migration_name() == db_migration.migration_name)
+        {
+            Some(fs_migration) => fs_migration,
+            None => continue,
+        };
+
+        let script = fs_migration.read_migration_script().map_err(ConnectorError::from)?;
+        let checksum = checksum::compute(&script);
+
+        connector
+            .migration_persistence(migrations_table_name)
+            .update_migration_checksum(&db_migration.migration_name, &checksum)
+            .await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn detect_failed_migrations(migrations_from_database: &[MigrationRecord]) -> CoreResult<()> {
     tracing::debug!("Checking for failed migrations.");
 
     let mut failed_migrations = migrations_from_database
@@ -148,3 +297,235 @@ fn detect_failed_migrations(migrations_from_database: &[MigrationRecord]) -> Cor
 
     Err(CoreError::user_facing(FoundFailedMigrations { details }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use migration_connector::MigrationPersistence;
+    use std::{fs, sync::Mutex};
+
+    fn temp_migrations_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("apply_migrations_test_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_migration(migrations_dir: &Path, name: &str, script: &str) {
+        let migration_dir = migrations_dir.join(name);
+        fs::create_dir_all(&migration_dir).unwrap();
+        fs::write(migration_dir.join("migration.sql"), script).unwrap();
+    }
+
+    fn migration_record(name: &str, checksum: Option<String>, rolled_back: bool) -> MigrationRecord {
+        MigrationRecord {
+            id: name.to_owned(),
+            finished_at: Some(Utc.timestamp_opt(0, 0).unwrap()),
+            migration_name: name.to_owned(),
+            logs: None,
+            rolled_back_at: rolled_back.then(|| Utc.timestamp_opt(0, 0).unwrap()),
+            started_at: Utc.timestamp_opt(0, 0).unwrap(),
+            applied_steps_count: 1,
+            checksum,
+        }
+    }
+
+    #[test]
+    fn detect_drift_is_ok_when_the_checksum_matches_the_script_on_disk() {
+        let dir = temp_migrations_dir("drift_ok");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+        let fs_migrations = list_migrations(&dir).unwrap();
+
+        let db_migrations = vec![migration_record(
+            "1_init",
+            Some(checksum::compute("CREATE TABLE a (id INT);")),
+            false,
+        )];
+
+        assert!(detect_drift(&fs_migrations, &db_migrations).is_ok());
+    }
+
+    #[test]
+    fn detect_drift_errs_when_an_applied_migration_was_edited_on_disk() {
+        let dir = temp_migrations_dir("drift_mismatch");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+        let fs_migrations = list_migrations(&dir).unwrap();
+
+        // The checksum on record doesn't match the script that's now on disk.
+        let db_migrations = vec![migration_record(
+            "1_init",
+            Some(checksum::compute("CREATE TABLE a (id BIGINT);")),
+            false,
+        )];
+
+        assert!(detect_drift(&fs_migrations, &db_migrations).is_err());
+    }
+
+    #[test]
+    fn detect_drift_ignores_rolled_back_migrations() {
+        let dir = temp_migrations_dir("drift_rolled_back");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+        let fs_migrations = list_migrations(&dir).unwrap();
+
+        // Would mismatch if compared, but the migration was rolled back, so it's excluded.
+        let db_migrations = vec![migration_record(
+            "1_init",
+            Some(checksum::compute("CREATE TABLE a (id BIGINT);")),
+            true,
+        )];
+
+        assert!(detect_drift(&fs_migrations, &db_migrations).is_ok());
+    }
+
+    #[test]
+    fn detect_drift_skips_migrations_with_no_stored_checksum() {
+        let dir = temp_migrations_dir("drift_no_checksum");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+        let fs_migrations = list_migrations(&dir).unwrap();
+
+        let db_migrations = vec![migration_record("1_init", None, false)];
+
+        assert!(detect_drift(&fs_migrations, &db_migrations).is_ok());
+    }
+
+    /// A `MigrationConnector` test double that records what it's asked to do instead of
+    /// talking to a real database.
+    #[derive(Default)]
+    struct TestConnector {
+        updated_checksums: Mutex<Vec<(String, String)>>,
+        supports_transactional_ddl: bool,
+        applied_in_transaction: Mutex<bool>,
+        applied_one_by_one: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl MigrationPersistence for TestConnector {
+        async fn initialize(&mut self) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn list_migrations(
+            &mut self,
+        ) -> Result<Result<Vec<MigrationRecord>, PersistenceNotInitializedError>, ConnectorError> {
+            Ok(Ok(Vec::new()))
+        }
+
+        async fn record_migration_started(
+            &mut self,
+            migration_name: &str,
+            _script: &str,
+            _checksum: &str,
+        ) -> Result<String, ConnectorError> {
+            self.applied_one_by_one.lock().unwrap().push(migration_name.to_owned());
+            Ok(migration_name.to_owned())
+        }
+
+        async fn update_migration_checksum(&mut self, migration_name: &str, checksum: &str) -> Result<(), ConnectorError> {
+            self.updated_checksums
+                .lock()
+                .unwrap()
+                .push((migration_name.to_owned(), checksum.to_owned()));
+            Ok(())
+        }
+
+        async fn record_successful_step(&mut self, _id: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn record_failed_step(&mut self, _id: &str, _logs: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn record_migration_finished(&mut self, _id: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+
+        async fn record_rolled_back(&mut self, _id: &str) -> Result<(), ConnectorError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MigrationConnector for TestConnector {
+        fn connector_type(&self) -> &'static str {
+            "test"
+        }
+
+        async fn acquire_lock(&mut self) -> migration_connector::ConnectorResult<()> {
+            Ok(())
+        }
+
+        fn migration_persistence(&mut self, _table_name: &str) -> &mut dyn MigrationPersistence {
+            self
+        }
+
+        async fn apply_script(&mut self, _migration_name: &str, _script: &str) -> migration_connector::ConnectorResult<()> {
+            Ok(())
+        }
+
+        fn supports_transactional_ddl(&self) -> bool {
+            self.supports_transactional_ddl
+        }
+
+        async fn apply_migrations_in_transaction(
+            &mut self,
+            migrations: &[(&str, String, String)],
+            _migrations_table_name: &str,
+        ) -> migration_connector::ConnectorResult<Vec<String>> {
+            *self.applied_in_transaction.lock().unwrap() = true;
+            Ok(migrations.iter().map(|(name, _, _)| (*name).to_owned()).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn apply_migrations_falls_back_to_one_by_one_when_transactional_ddl_is_unsupported() {
+        let dir = temp_migrations_dir("fallback");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+
+        let mut connector = TestConnector {
+            supports_transactional_ddl: false,
+            ..Default::default()
+        };
+
+        let input = ApplyMigrationsInput {
+            migrations_directory_path: dir.to_str().unwrap().to_owned(),
+            use_single_transaction: true,
+            migrations_table_name: None,
+        };
+
+        apply_migrations(input, &mut connector).await.unwrap();
+
+        assert!(!*connector.applied_in_transaction.lock().unwrap());
+        assert_eq!(connector.applied_one_by_one.lock().unwrap().as_slice(), &["1_init".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn backfill_checksums_fills_in_missing_checksums_and_leaves_others_alone() {
+        let dir = temp_migrations_dir("backfill");
+        write_migration(&dir, "1_init", "CREATE TABLE a (id INT);");
+        write_migration(&dir, "2_add_column", "ALTER TABLE a ADD COLUMN b INT;");
+        let fs_migrations = list_migrations(&dir).unwrap();
+
+        let db_migrations = vec![
+            migration_record("1_init", None, false),
+            migration_record(
+                "2_add_column",
+                Some(checksum::compute("ALTER TABLE a ADD COLUMN b INT;")),
+                false,
+            ),
+        ];
+
+        let mut connector = TestConnector::default();
+
+        backfill_checksums(&fs_migrations, &db_migrations, &mut connector, DEFAULT_MIGRATIONS_TABLE_NAME)
+            .await
+            .unwrap();
+
+        let updated = connector.updated_checksums.lock().unwrap();
+        assert_eq!(updated.as_slice(), &[(
+            "1_init".to_owned(),
+            checksum::compute("CREATE TABLE a (id INT);"),
+        )]);
+    }
+}
@@ -0,0 +1,5 @@
+mod apply_migrations;
+mod revert_migrations;
+
+pub use apply_migrations::apply_migrations;
+pub use revert_migrations::revert_migrations;
@@ -0,0 +1,199 @@
+use super::apply_migrations::detect_failed_migrations;
+use crate::{json_rpc::types::*, CoreError, CoreResult};
+use migration_connector::{
+    migration_persistence::DEFAULT_MIGRATIONS_TABLE_NAME,
+    migrations_directory::list_migrations, ConnectorError, MigrationConnector, MigrationRecord,
+    PersistenceNotInitializedError,
+};
+use std::path::Path;
+use user_facing_errors::migration_engine::{MigrationRollbackMissing, MigrationTargetNotFound};
+
+/// Roll back previously applied migrations, in reverse order of application, down to (and
+/// including) a target migration name, or a number of steps.
+pub async fn revert_migrations(
+    input: RevertMigrationsInput,
+    connector: &mut dyn MigrationConnector,
+) -> CoreResult<RevertMigrationsOutput> {
+    let migrations_table_name = input
+        .migrations_table_name
+        .as_deref()
+        .unwrap_or(DEFAULT_MIGRATIONS_TABLE_NAME);
+
+    connector.acquire_lock().await?;
+
+    connector.migration_persistence(migrations_table_name).initialize().await?;
+
+    let migrations_from_filesystem = list_migrations(Path::new(&input.migrations_directory_path))?;
+    let migrations_from_database = connector
+        .migration_persistence(migrations_table_name)
+        .list_migrations()
+        .await?
+        .map_err(PersistenceNotInitializedError::into_connector_error)?;
+
+    detect_failed_migrations(&migrations_from_database)?;
+
+    let to_revert = migrations_in_revert_range(&migrations_from_database, &input.target)?;
+
+    // Every migration we are about to roll back must have a `down.sql` next to it, or we bail
+    // out before touching the database at all.
+    let missing_down_scripts: Vec<&str> = to_revert
+        .iter()
+        .filter(|db_migration| {
+            !migrations_from_filesystem
+                .iter()
+                .any(|fs_migration| fs_migration.migration_name() == db_migration.migration_name && fs_migration.has_rollback_script())
+        })
+        .map(|db_migration| db_migration.migration_name.as_str())
+        .collect();
+
+    if !missing_down_scripts.is_empty() {
+        return Err(CoreError::user_facing(MigrationRollbackMissing {
+            migration_names: missing_down_scripts.join(", "),
+        }));
+    }
+
+    let mut rolled_back_migration_names = Vec::with_capacity(to_revert.len());
+
+    for db_migration in to_revert {
+        let fs_migration = migrations_from_filesystem
+            .iter()
+            .find(|fs_migration| fs_migration.migration_name() == db_migration.migration_name)
+            .expect("checked for presence above");
+
+        let down_script = fs_migration
+            .read_rollback_script()
+            .map_err(ConnectorError::from)?
+            .expect("checked for presence above");
+
+        tracing::info!("Reverting `{}`", db_migration.migration_name);
+
+        connector
+            .apply_script(db_migration.migration_name.as_str(), &down_script)
+            .await?;
+
+        connector
+            .migration_persistence(migrations_table_name)
+            .record_rolled_back(&db_migration.id)
+            .await?;
+
+        rolled_back_migration_names.push(db_migration.migration_name.clone());
+    }
+
+    Ok(RevertMigrationsOutput {
+        rolled_back_migration_names,
+    })
+}
+
+/// Given the migrations recorded in the database, in application order, return the ones that
+/// should be reverted to reach `target`, most recently applied first.
+fn migrations_in_revert_range<'a>(
+    migrations_from_database: &'a [MigrationRecord],
+    target: &RevertMigrationsTarget,
+) -> CoreResult<Vec<&'a MigrationRecord>> {
+    let applied: Vec<&MigrationRecord> = migrations_from_database
+        .iter()
+        .filter(|migration| migration.rolled_back_at.is_none())
+        .collect();
+
+    let mut reversed = applied;
+    reversed.reverse(); // most recently applied first
+
+    let range = match target {
+        RevertMigrationsTarget::Steps(steps) => reversed.into_iter().take(*steps as usize).collect(),
+        RevertMigrationsTarget::MigrationName(name) => {
+            let mut range = Vec::new();
+
+            for migration in reversed {
+                range.push(migration);
+
+                if &migration.migration_name == name {
+                    return Ok(range);
+                }
+            }
+
+            // The target migration is not among the currently applied migrations: it may be
+            // misspelled, already rolled back, or never applied. Either way, silently doing
+            // nothing would hide the mistake from the user.
+            return Err(CoreError::user_facing(MigrationTargetNotFound {
+                migration_name: name.clone(),
+            }));
+        }
+    };
+
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn migration(name: &str, rolled_back: bool) -> MigrationRecord {
+        MigrationRecord {
+            id: name.to_owned(),
+            finished_at: Some(Utc.timestamp_opt(0, 0).unwrap()),
+            migration_name: name.to_owned(),
+            logs: None,
+            rolled_back_at: rolled_back.then(|| Utc.timestamp_opt(0, 0).unwrap()),
+            started_at: Utc.timestamp_opt(0, 0).unwrap(),
+            applied_steps_count: 1,
+            checksum: None,
+        }
+    }
+
+    fn names(records: &[&MigrationRecord]) -> Vec<&str> {
+        records.iter().map(|record| record.migration_name.as_str()).collect()
+    }
+
+    #[test]
+    fn steps_selects_the_n_most_recently_applied_migrations_in_reverse_order() {
+        let migrations = vec![migration("1_init", false), migration("2_add_column", false), migration("3_add_index", false)];
+
+        let range = migrations_in_revert_range(&migrations, &RevertMigrationsTarget::Steps(2)).unwrap();
+
+        assert_eq!(names(&range), vec!["3_add_index", "2_add_column"]);
+    }
+
+    #[test]
+    fn steps_greater_than_the_applied_count_returns_everything() {
+        let migrations = vec![migration("1_init", false), migration("2_add_column", false)];
+
+        let range = migrations_in_revert_range(&migrations, &RevertMigrationsTarget::Steps(10)).unwrap();
+
+        assert_eq!(names(&range), vec!["2_add_column", "1_init"]);
+    }
+
+    #[test]
+    fn migration_name_selects_down_to_and_including_the_target() {
+        let migrations = vec![migration("1_init", false), migration("2_add_column", false), migration("3_add_index", false)];
+
+        let range = migrations_in_revert_range(
+            &migrations,
+            &RevertMigrationsTarget::MigrationName("2_add_column".to_owned()),
+        )
+        .unwrap();
+
+        assert_eq!(names(&range), vec!["3_add_index", "2_add_column"]);
+    }
+
+    #[test]
+    fn steps_ignores_already_rolled_back_migrations() {
+        let migrations = vec![migration("1_init", false), migration("2_add_column", true)];
+
+        let range = migrations_in_revert_range(&migrations, &RevertMigrationsTarget::Steps(10)).unwrap();
+
+        assert_eq!(names(&range), vec!["1_init"]);
+    }
+
+    #[test]
+    fn migration_name_not_found_among_applied_migrations_is_an_error() {
+        let migrations = vec![migration("1_init", false)];
+
+        let result = migrations_in_revert_range(
+            &migrations,
+            &RevertMigrationsTarget::MigrationName("typo_name".to_owned()),
+        );
+
+        assert!(result.is_err());
+    }
+}
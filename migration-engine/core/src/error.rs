@@ -0,0 +1,20 @@
+use migration_connector::ConnectorError;
+use user_facing_errors::UserFacingError;
+
+pub type CoreResult<T> = Result<T, CoreError>;
+
+/// The top-level error type for the migration engine core.
+#[derive(Debug, thiserror::Error)]
+pub enum CoreError {
+    #[error("{0}")]
+    ConnectorError(#[from] ConnectorError),
+
+    #[error("{0}")]
+    UserFacing(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl CoreError {
+    pub fn user_facing(err: impl UserFacingError + 'static) -> Self {
+        CoreError::UserFacing(Box::new(err))
+    }
+}
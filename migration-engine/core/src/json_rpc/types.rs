@@ -0,0 +1,56 @@
+//! The input and output types for the commands exposed over the migration engine's JSON-RPC API.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyMigrationsInput {
+    /// The location of the migrations directory.
+    pub migrations_directory_path: String,
+    /// If true, apply all unapplied migrations inside a single database transaction instead of
+    /// one transaction per migration, so a failure rolls back the whole batch. Falls back to
+    /// the per-migration behavior if the connector does not support transactional DDL.
+    #[serde(default)]
+    pub use_single_transaction: bool,
+    /// The name of the bookkeeping table to use instead of `_prisma_migrations`, for users who
+    /// run several schemas against one database. Defaults to `_prisma_migrations`.
+    #[serde(default)]
+    pub migrations_table_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyMigrationsOutput {
+    pub applied_migration_names: Vec<String>,
+}
+
+/// The target to revert to: either a specific migration name, or a number of steps to roll back.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", untagged)]
+pub enum RevertMigrationsTarget {
+    /// Roll back down to (and including) the migration with this name.
+    MigrationName(String),
+    /// Roll back this many applied migrations, most recent first.
+    Steps(u32),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertMigrationsInput {
+    /// The location of the migrations directory.
+    pub migrations_directory_path: String,
+    /// How far to roll back.
+    pub target: RevertMigrationsTarget,
+    /// The name of the bookkeeping table, mirroring [`ApplyMigrationsInput::migrations_table_name`].
+    /// Defaults to `_prisma_migrations`.
+    #[serde(default)]
+    pub migrations_table_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertMigrationsOutput {
+    /// The names of the migrations that were rolled back, in the order they were reverted
+    /// (most recently applied first).
+    pub rolled_back_migration_names: Vec<String>,
+}
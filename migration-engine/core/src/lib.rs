@@ -0,0 +1,7 @@
+mod commands;
+mod error;
+
+pub mod json_rpc;
+
+pub use commands::{apply_migrations, revert_migrations};
+pub use error::{CoreError, CoreResult};
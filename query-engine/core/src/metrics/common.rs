@@ -0,0 +1,33 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+
+/// A point-in-time snapshot of every metric registered with the query engine.
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub counters: Vec<Metric>,
+    pub gauges: Vec<Metric>,
+    pub histograms: Vec<Metric>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Metric {
+    pub key: String,
+    pub description: String,
+    pub labels: IndexMap<String, String>,
+    pub value: MetricValue,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum MetricValue {
+    Counter(u64),
+    Gauge(f64),
+    Histogram(HistogramValue),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramValue {
+    pub buckets: Vec<(f64, u64)>,
+    pub sum: f64,
+    pub count: u64,
+}
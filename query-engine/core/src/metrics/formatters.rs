@@ -4,6 +4,7 @@ use metrics_exporter_prometheus::formatting::{
     sanitize_description, sanitize_label_key, sanitize_label_value, write_help_line, write_metric_line, write_type_line,
 };
 use serde_json::Value;
+use std::fmt::Write as _;
 
 fn create_label_string(labels: &IndexMap<String, String>) -> Vec<String> {
     labels
@@ -101,3 +102,122 @@ pub(crate) fn metrics_to_prometheus(snapshot: Snapshot) -> String {
 
     output
 }
+
+/// Units recognized by the [`metric_unit`] heuristic. OpenMetrics only emits a `# UNIT` line
+/// when the metric name carries one of these suffixes (see
+/// https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md#unit).
+const KNOWN_UNITS: &[&str] = &["seconds", "bytes", "ratio", "percent"];
+
+/// If `key` ends with a recognized unit suffix (e.g. `query_duration_seconds`), return that
+/// unit (`seconds`).
+fn metric_unit(key: &str) -> Option<&'static str> {
+    KNOWN_UNITS.iter().copied().find(|unit| key.ends_with(&format!("_{unit}")))
+}
+
+pub(crate) fn metrics_to_openmetrics(snapshot: Snapshot) -> String {
+    let Snapshot {
+        counters,
+        histograms,
+        gauges,
+    } = snapshot;
+
+    let mut output = String::new();
+
+    for counter in counters {
+        // OpenMetrics requires the sample line of a counter series to be suffixed with
+        // `_total`, but HELP/TYPE/UNIT must still name the metric family, without the suffix.
+        // Counters already named with a `_total` suffix (e.g. `queries_total`) are left as-is,
+        // since appending again would produce `queries_total_total`.
+        let family_name = counter.key.strip_suffix("_total").unwrap_or(counter.key.as_str());
+        let sample_name = format!("{family_name}_total");
+        let desc = sanitize_description(counter.description.as_str());
+
+        write_help_line(&mut output, family_name, desc.as_str());
+
+        if let Some(unit) = metric_unit(family_name) {
+            writeln!(output, "# UNIT {family_name} {unit}").unwrap();
+        }
+
+        write_type_line(&mut output, family_name, "counter");
+        let labels = create_label_string(&counter.labels);
+
+        if let MetricValue::Counter(value) = counter.value {
+            write_metric_line::<&str, u64>(&mut output, sample_name.as_str(), None, &labels, None, value);
+        }
+        output.push('\n');
+    }
+
+    for gauge in gauges {
+        let desc = sanitize_description(gauge.description.as_str());
+        write_help_line(&mut output, gauge.key.as_str(), desc.as_str());
+
+        if let Some(unit) = metric_unit(gauge.key.as_str()) {
+            writeln!(output, "# UNIT {} {unit}", gauge.key.as_str()).unwrap();
+        }
+
+        write_type_line(&mut output, gauge.key.as_str(), "gauge");
+        let labels = create_label_string(&gauge.labels);
+
+        if let MetricValue::Gauge(value) = gauge.value {
+            write_metric_line::<&str, f64>(&mut output, &gauge.key.as_str(), None, &labels, None, value);
+        }
+        output.push('\n');
+    }
+
+    for histogram in histograms {
+        let desc = sanitize_description(histogram.description.as_str());
+        write_help_line(&mut output, histogram.key.as_str(), desc.as_str());
+
+        if let Some(unit) = metric_unit(histogram.key.as_str()) {
+            writeln!(output, "# UNIT {} {unit}", histogram.key.as_str()).unwrap();
+        }
+
+        write_type_line(&mut output, histogram.key.as_str(), "histogram");
+        let labels = create_label_string(&histogram.labels);
+
+        if let MetricValue::Histogram(histogram_values) = histogram.value {
+            for (le, count) in histogram_values.buckets {
+                write_metric_line(
+                    &mut output,
+                    histogram.key.as_str(),
+                    Some("bucket"),
+                    &labels,
+                    Some(("le", le)),
+                    count,
+                );
+            }
+
+            // The OpenMetrics spec requires a `+Inf` bucket even though it duplicates `_count`.
+            write_metric_line(
+                &mut output,
+                histogram.key.as_str(),
+                Some("bucket"),
+                &labels,
+                Some(("le", "+Inf")),
+                histogram_values.count,
+            );
+            write_metric_line::<&str, f64>(
+                &mut output,
+                histogram.key.as_str(),
+                Some("sum"),
+                &labels,
+                None,
+                histogram_values.sum,
+            );
+            write_metric_line::<&str, u64>(
+                &mut output,
+                histogram.key.as_str(),
+                Some("count"),
+                &labels,
+                None,
+                histogram_values.count,
+            );
+        }
+
+        output.push('\n');
+    }
+
+    output.push_str("# EOF\n");
+
+    output
+}
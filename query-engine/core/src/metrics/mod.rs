@@ -0,0 +1,47 @@
+mod common;
+mod formatters;
+
+pub use common::{HistogramValue, Metric, MetricValue, Snapshot};
+
+use formatters::{metrics_to_json, metrics_to_openmetrics, metrics_to_prometheus};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+/// Which exposition format to render a [`Snapshot`] as. Selected by the caller of
+/// [`Snapshot::render`] (e.g. based on content negotiation on the metrics HTTP endpoint).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetricFormat {
+    #[default]
+    Json,
+    Prometheus,
+    OpenMetrics,
+}
+
+/// The `Content-Type` to serve alongside [`Snapshot::render`]'s output for a given format.
+pub fn content_type(format: MetricFormat) -> &'static str {
+    match format {
+        MetricFormat::Json => "application/json",
+        MetricFormat::Prometheus => "text/plain; version=0.0.4",
+        MetricFormat::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToJsonOptions {
+    pub global_labels: IndexMap<String, String>,
+}
+
+impl Snapshot {
+    pub fn to_json(self, _options: ToJsonOptions) -> Value {
+        metrics_to_json(self)
+    }
+
+    /// Render this snapshot in the requested exposition format.
+    pub fn render(self, format: MetricFormat) -> String {
+        match format {
+            MetricFormat::Json => metrics_to_json(self).to_string(),
+            MetricFormat::Prometheus => metrics_to_prometheus(self),
+            MetricFormat::OpenMetrics => metrics_to_openmetrics(self),
+        }
+    }
+}